@@ -17,7 +17,7 @@ use serde_json::Value;
 /// Represents position on the game board
 ///
 /// 0, 0 are the x, y coordinates indicating the top-leftmost position
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Position {
     pub x: usize,
     pub y: usize,
@@ -33,6 +33,29 @@ impl Position {
     }
 }
 
+/// An axis-aligned rectangle on the game board, given by its top-left and
+/// bottom-right corners
+///
+/// Both corners are inclusive, 0-indexed positions (the bottom-right corner
+/// of a `cols` x `rows` rectangle starting at `top_left` is `top_left +
+/// cols - 1, top_left + rows - 1`, hence the `-1` seen wherever bounds are
+/// built from a width/height).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rectangle {
+    pub top_left: Position,
+    pub bottom_right: Position,
+}
+
+impl Rectangle {
+    pub fn width(&self) -> usize {
+        self.bottom_right.x - self.top_left.x + 1
+    }
+
+    pub fn height(&self) -> usize {
+        self.bottom_right.y - self.top_left.y + 1
+    }
+}
+
 /// A simple 2d array
 ///
 /// rows is the number of y indices
@@ -293,6 +316,18 @@ mod tests {
         array[Position { x: 14, y: 0 }] = 5;
     }
 
+    /* Rectangle tests */
+
+    #[test]
+    fn test_rectangle_width_height() {
+        let rect = Rectangle {
+            top_left: Position { x: 2, y: 5 },
+            bottom_right: Position { x: 4, y: 6 },
+        };
+        assert_eq!(rect.width(), 3);
+        assert_eq!(rect.height(), 2);
+    }
+
     /* Bitmap Tests */
 
     #[test]
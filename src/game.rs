@@ -7,9 +7,13 @@
 ********************************************************************/
 
 use crate::engine;
+use crate::render::Renderer;
 use std::cmp;
+use std::collections::{hash_map::DefaultHasher, BTreeMap, HashMap, HashSet};
 use std::fmt;
 use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io;
 //use std::ops::{Index, IndexMut};
 use serde::{Deserialize, Serialize};
 
@@ -78,6 +82,17 @@ impl Sprite {
         &self.bounds
     }
 
+    pub fn set_pos(&mut self, new_pos: engine::Position) {
+        let cols = self.pixels.get_data().get_cols();
+        let rows = self.pixels.get_data().get_rows();
+        self.pos = new_pos;
+        self.bounds = engine::Rectangle {
+            top_left: engine::Position { x: new_pos.x, y: new_pos.y },
+            // Bottom-right corner of 0-indexed rectangle, remember the -1
+            bottom_right: engine::Position { x: new_pos.x + cols - 1, y: new_pos.y + rows - 1 },
+        };
+    }
+
     pub fn intersect(&self, other: &Sprite) -> bool {
         // An exercise in destructuring
         let Sprite {
@@ -108,10 +123,8 @@ impl Sprite {
             other_top_side > self_bottom_side ||
             other_right_side < self_left_side ||
             other_bottom_side < self_top_side {
-                println!("False");
                 false
         } else {
-            println!("True");
             // Find overlapping range
             let common_start_x = cmp::max(self_left_side, other_left_side);
             let common_start_y = cmp::max(self_top_side, other_top_side);
@@ -149,58 +162,519 @@ pub enum BoardError {
     OutOfRange,
 }
 
+/// A stable reference to a `Sprite` stored in a `SpriteSlab`
+///
+/// The generation is bumped every time the slot at `index` is vacated, so a
+/// handle kept around after its sprite is removed (and the slot reused by a
+/// later insert) is rejected by `SpriteSlab::get` instead of silently
+/// resolving to the wrong sprite.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SpriteHandle {
+    index: usize,
+    generation: u32,
+}
+
+#[derive(Debug)]
+enum Slot {
+    Occupied(u32, Sprite),
+    Vacant(u32, Option<usize>),
+}
+
+/// A generational-index slab allocator for sprites
+///
+/// Supports O(1) insert/remove/lookup while keeping `SpriteHandle`s stable
+/// across removals: removing a sprite frees its slot onto a free list (bumping
+/// the slot's generation) and a later insert reuses that slot rather than
+/// growing the backing `Vec`.
+#[derive(Debug)]
+struct SpriteSlab {
+    slots: Vec<Slot>,
+    free_head: Option<usize>,
+    len: usize,
+}
+
+impl SpriteSlab {
+    fn new() -> Self {
+        Self {
+            slots: Vec::new(),
+            free_head: None,
+            len: 0,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn insert(&mut self, sprite: Sprite) -> SpriteHandle {
+        self.len += 1;
+        match self.free_head {
+            Some(index) => {
+                let generation = match self.slots[index] {
+                    Slot::Vacant(generation, next_free) => {
+                        self.free_head = next_free;
+                        generation
+                    }
+                    Slot::Occupied(..) => panic!("free list pointed at an occupied slot"),
+                };
+                self.slots[index] = Slot::Occupied(generation, sprite);
+                SpriteHandle { index, generation }
+            }
+            None => {
+                let index = self.slots.len();
+                self.slots.push(Slot::Occupied(0, sprite));
+                SpriteHandle { index, generation: 0 }
+            }
+        }
+    }
+
+    fn remove(&mut self, handle: SpriteHandle) -> Option<Sprite> {
+        match self.slots.get(handle.index) {
+            Some(Slot::Occupied(generation, _)) if *generation == handle.generation => {
+                let next_free = self.free_head;
+                let old = std::mem::replace(
+                    &mut self.slots[handle.index],
+                    Slot::Vacant(handle.generation.wrapping_add(1), next_free),
+                );
+                self.free_head = Some(handle.index);
+                self.len -= 1;
+                match old {
+                    Slot::Occupied(_, sprite) => Some(sprite),
+                    Slot::Vacant(..) => unreachable!(),
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn get(&self, handle: SpriteHandle) -> Option<&Sprite> {
+        match self.slots.get(handle.index) {
+            Some(Slot::Occupied(generation, sprite)) if *generation == handle.generation => {
+                Some(sprite)
+            }
+            _ => None,
+        }
+    }
+
+    fn get_mut(&mut self, handle: SpriteHandle) -> Option<&mut Sprite> {
+        match self.slots.get_mut(handle.index) {
+            Some(Slot::Occupied(generation, sprite)) if *generation == handle.generation => {
+                Some(sprite)
+            }
+            _ => None,
+        }
+    }
+
+    fn iter(&self) -> impl Iterator<Item = (SpriteHandle, &Sprite)> {
+        self.slots.iter().enumerate().filter_map(|(index, slot)| match slot {
+            Slot::Occupied(generation, sprite) => {
+                Some((SpriteHandle { index, generation: *generation }, sprite))
+            }
+            Slot::Vacant(..) => None,
+        })
+    }
+}
+
+// Sprites are persisted as a plain array, same as before the slab existed;
+// handles are runtime-only and get reassigned (starting fresh at generation
+// 0) when a board is deserialized, same as the spatial grid they're used in.
+impl Serialize for SpriteSlab {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let sprites: Vec<&Sprite> = self.iter().map(|(_, sprite)| sprite).collect();
+        sprites.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for SpriteSlab {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let sprites = Vec::<Sprite>::deserialize(deserializer)?;
+        let mut slab = SpriteSlab::new();
+        for sprite in sprites {
+            slab.insert(sprite);
+        }
+        Ok(slab)
+    }
+}
+
+/// A uniform spatial-hash grid used as a broad phase for collision checks
+///
+/// Each cell maps to the handles of every sprite whose `bounds` AABB
+/// overlaps that cell. A sprite spanning multiple cells is registered in
+/// all of them, so candidate lookups just union the cells a query rectangle
+/// touches instead of scanning every sprite.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SpatialGrid {
+    cell_size: usize,
+    cells: HashMap<(i32, i32), Vec<SpriteHandle>>,
+}
+
+impl SpatialGrid {
+    // Cells a rectangle's bounds span, inclusive on both ends
+    fn cells_for(&self, bounds: &engine::Rectangle) -> Vec<(i32, i32)> {
+        let cell_size = cmp::max(self.cell_size, 1) as i32;
+        let min_cx = bounds.top_left.x as i32 / cell_size;
+        let max_cx = bounds.bottom_right.x as i32 / cell_size;
+        let min_cy = bounds.top_left.y as i32 / cell_size;
+        let max_cy = bounds.bottom_right.y as i32 / cell_size;
+        let mut cells = Vec::new();
+        for cy in min_cy..=max_cy {
+            for cx in min_cx..=max_cx {
+                cells.push((cx, cy));
+            }
+        }
+        cells
+    }
+
+    fn insert(&mut self, handle: SpriteHandle, bounds: &engine::Rectangle) {
+        for cell in self.cells_for(bounds) {
+            self.cells.entry(cell).or_default().push(handle);
+        }
+    }
+
+    // Must be called with a sprite's *old* bounds before it moves or is
+    // removed, otherwise stale entries linger in cells it no longer occupies
+    fn remove(&mut self, handle: SpriteHandle, bounds: &engine::Rectangle) {
+        for cell in self.cells_for(bounds) {
+            if let Some(handles) = self.cells.get_mut(&cell) {
+                handles.retain(|&h| h != handle);
+            }
+        }
+    }
+
+    fn candidates(&self, bounds: &engine::Rectangle) -> Vec<SpriteHandle> {
+        let mut found = Vec::new();
+        for cell in self.cells_for(bounds) {
+            if let Some(handles) = self.cells.get(&cell) {
+                for &handle in handles {
+                    if !found.contains(&handle) {
+                        found.push(handle);
+                    }
+                }
+            }
+        }
+        found
+    }
+}
+
+/// An interval-based occupancy index over sprite bounds
+///
+/// Indexes each sprite's x-interval and y-interval (inclusive, 0-indexed,
+/// matching the `-1` bottom-right convention used throughout this crate) in
+/// separate `BTreeMap`s ordered by interval start. A query rectangle is
+/// resolved with two ordered range lookups, one per axis, pruning to
+/// intervals that start no later than the query ends, followed by
+/// intersecting the two candidate sets. Distinct sprites sharing an
+/// identical interval (e.g. a row of aligned invaders) are stored together
+/// under that one key rather than colliding.
+#[derive(Debug, Default)]
+struct IntervalIndex {
+    x: BTreeMap<(usize, usize), Vec<SpriteHandle>>,
+    y: BTreeMap<(usize, usize), Vec<SpriteHandle>>,
+}
+
+impl IntervalIndex {
+    fn insert(&mut self, handle: SpriteHandle, bounds: &engine::Rectangle) {
+        Self::insert_axis(&mut self.x, (bounds.top_left.x, bounds.bottom_right.x), handle);
+        Self::insert_axis(&mut self.y, (bounds.top_left.y, bounds.bottom_right.y), handle);
+    }
+
+    fn remove(&mut self, handle: SpriteHandle, bounds: &engine::Rectangle) {
+        Self::remove_axis(&mut self.x, (bounds.top_left.x, bounds.bottom_right.x), handle);
+        Self::remove_axis(&mut self.y, (bounds.top_left.y, bounds.bottom_right.y), handle);
+    }
+
+    fn insert_axis(index: &mut BTreeMap<(usize, usize), Vec<SpriteHandle>>, key: (usize, usize), handle: SpriteHandle) {
+        index.entry(key).or_default().push(handle);
+    }
+
+    fn remove_axis(index: &mut BTreeMap<(usize, usize), Vec<SpriteHandle>>, key: (usize, usize), handle: SpriteHandle) {
+        if let Some(handles) = index.get_mut(&key) {
+            handles.retain(|&h| h != handle);
+            if handles.is_empty() {
+                index.remove(&key);
+            }
+        }
+    }
+
+    // Intervals are keyed (start, end) so ordering by start lets us prune
+    // to everything starting at or before the query's end in one range
+    // lookup; each surviving candidate's own end still has to be checked
+    // individually since a later-starting interval can still end earlier
+    fn candidates_on_axis(
+        index: &BTreeMap<(usize, usize), Vec<SpriteHandle>>,
+        query_start: usize,
+        query_end: usize,
+    ) -> Vec<SpriteHandle> {
+        let mut found = Vec::new();
+        for (&(_, end), handles) in index.range(..=(query_end, usize::MAX)) {
+            if end >= query_start {
+                found.extend_from_slice(handles);
+            }
+        }
+        found
+    }
+
+    fn overlapping(&self, query: &engine::Rectangle) -> Vec<SpriteHandle> {
+        let x_candidates = Self::candidates_on_axis(&self.x, query.top_left.x, query.bottom_right.x);
+        let y_candidates: HashSet<SpriteHandle> =
+            Self::candidates_on_axis(&self.y, query.top_left.y, query.bottom_right.y)
+                .into_iter()
+                .collect();
+        x_candidates
+            .into_iter()
+            .filter(|handle| y_candidates.contains(handle))
+            .collect()
+    }
+}
+
 /// Represents the game board, all the sprites, and the actions that
 /// can be taken by each of the sprites
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Board {
-    sprites: Vec<Sprite>,
+    sprites: SpriteSlab,
     screen: engine::Bitmap,
+    #[serde(skip)]
+    grid: SpatialGrid,
+    #[serde(skip)]
+    intervals: IntervalIndex,
+    // The bounds and pixel fingerprint each live sprite had as of the last
+    // update(), so the next update() can tell which sprites moved, were
+    // repainted in place, or were removed, and limit redraw work to the
+    // cells that actually changed
+    #[serde(skip)]
+    previous_frame: HashMap<SpriteHandle, (engine::Rectangle, u64)>,
 }
 
 impl Board {
     pub fn new(cols: usize, rows: usize, fg: u8, bg: u8) -> Self {
         Self {
-            sprites: Vec::<Sprite>::new(),
+            sprites: SpriteSlab::new(),
             screen: engine::Bitmap::new(cols, rows, fg, bg),
+            grid: SpatialGrid::default(),
+            intervals: IntervalIndex::default(),
+            previous_frame: HashMap::new(),
         }
     }
 
     pub fn build_from_str(data: &str) -> Self {
-        let b: Board = serde_json::from_str(&data).expect("Could not deserialize Board");
+        let mut b: Board = serde_json::from_str(&data).expect("Could not deserialize Board");
+        b.rebuild_indexes();
         b
     }
 
+    // The grid and interval index are both derived data and aren't
+    // serialized, so they have to be reconstructed from the sprites that
+    // were deserialized
+    fn rebuild_indexes(&mut self) {
+        self.grid = SpatialGrid::default();
+        self.intervals = IntervalIndex::default();
+        let entries: Vec<(SpriteHandle, engine::Rectangle)> = self
+            .sprites
+            .iter()
+            .map(|(handle, sprite)| (handle, *sprite.get_bounds()))
+            .collect();
+        for (handle, bounds) in entries {
+            self.index_insert(handle, &bounds);
+        }
+    }
+
+    fn index_insert(&mut self, handle: SpriteHandle, bounds: &engine::Rectangle) {
+        if self.grid.cell_size == 0 {
+            self.grid.cell_size = Self::cell_size_for(bounds);
+        }
+        self.grid.insert(handle, bounds);
+        self.intervals.insert(handle, bounds);
+    }
+
+    fn index_remove(&mut self, handle: SpriteHandle, bounds: &engine::Rectangle) {
+        self.grid.remove(handle, bounds);
+        self.intervals.remove(handle, bounds);
+    }
+
+    /// Sprites whose bounds overlap the given rectangle
+    ///
+    /// Backed by the interval index rather than the spatial grid, since
+    /// it's the cheaper structure to query when the rectangle in question
+    /// isn't a newly-proposed sprite's own bounds (e.g. a hit-test against
+    /// an arbitrary region of the board).
+    pub fn sprites_overlapping(&self, query: &engine::Rectangle) -> Vec<SpriteHandle> {
+        self.intervals.overlapping(query)
+    }
+
+    // Picks a cell size from a representative sprite's bounds so that a
+    // typical sprite spans only a handful of cells
+    fn cell_size_for(bounds: &engine::Rectangle) -> usize {
+        let mut dims = [bounds.width(), bounds.height()];
+        dims.sort_unstable();
+        cmp::max(dims[dims.len() / 2], 1)
+    }
+
     pub fn build_from_file(path: &str) -> Self {
         let data = fs::read_to_string(path).expect("Could not read Board file");
         Board::build_from_str(&data)
     }
 
-    // Brute-force rescan of entire board (or maybe rescan just Sprites)
-    // Pixels occupied by a Sprite are colored fg, and rest bg
-    //
-    // TODO Likely will be a major performance bottleneck in future
-    // TODO Better idea in future is to only look at positions that
-    //  a Sprite previously occupied
-    pub fn update(&mut self) {
-        self.screen.reset();
-        
-        for sprite in &self.sprites {
-            for y in 0..sprite.pixels.get_data().get_rows() {
-                for x in 0..sprite.pixels.get_data().get_cols() {
-                    println!("y = {}, x = {}", y, x);
-                    if self.screen.get_data()[engine::Position { x: (x+sprite.get_pos().get_x()), y: (y+sprite.get_pos().get_y()) }] != self.screen.get_bg() {
-                        panic!("Failed to update board")
+    /// Moves a sprite to a new position, keeping the grid and interval
+    /// index in sync
+    ///
+    /// Returns `false` if the handle is stale.
+    pub fn move_sprite(&mut self, handle: SpriteHandle, new_pos: engine::Position) -> bool {
+        let old_bounds = match self.sprites.get(handle) {
+            Some(sprite) => *sprite.get_bounds(),
+            None => return false,
+        };
+        self.sprites.get_mut(handle).unwrap().set_pos(new_pos);
+        let new_bounds = *self.sprites.get(handle).unwrap().get_bounds();
+
+        // A sprite spanning multiple grid cells (or sharing interval keys)
+        // is registered under all of them, so the old entries must be
+        // cleared before it's re-inserted under its new bounds
+        self.index_remove(handle, &old_bounds);
+        self.index_insert(handle, &new_bounds);
+        true
+    }
+
+    // A cheap stand-in for comparing a sprite's pixels wholesale: two sprites
+    // with the same fingerprint are assumed to look identical. Lets an
+    // in-place repaint (same bounds, new pixels -- e.g. a marching animation
+    // frame) be told apart from a truly unchanged sprite
+    fn pixel_fingerprint(sprite: &Sprite) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        sprite.pixels.get_data().get_elements().hash(&mut hasher);
+        hasher.finish()
+    }
+
+    // Only the cells a sprite vacated, newly occupies, or repainted in place
+    // need to be touched; a sprite whose position and pixels both haven't
+    // changed contributes nothing
+    fn dirty_rects(&self) -> Vec<engine::Rectangle> {
+        let mut dirty = Vec::new();
+        let mut seen = HashSet::new();
+
+        for (handle, sprite) in self.sprites.iter() {
+            seen.insert(handle);
+            let bounds = *sprite.get_bounds();
+            let fingerprint = Self::pixel_fingerprint(sprite);
+            match self.previous_frame.get(&handle) {
+                Some((prev, prev_fingerprint)) if *prev == bounds && *prev_fingerprint == fingerprint => {}
+                Some((prev, _)) => {
+                    dirty.push(*prev);
+                    dirty.push(bounds);
+                }
+                None => dirty.push(bounds),
+            }
+        }
+
+        // A sprite removed since the last update vacates the cells it used
+        // to occupy, and those still need to be cleared back to bg
+        for (handle, (prev, _)) in &self.previous_frame {
+            if !seen.contains(handle) {
+                dirty.push(*prev);
+            }
+        }
+
+        Self::coalesce_rects(dirty)
+    }
+
+    // Repeatedly merges any two overlapping rectangles into their bounding
+    // union until no pair overlaps, so later clearing/redraw passes don't
+    // do duplicate work over cells that appear in more than one dirty rect
+    fn coalesce_rects(mut rects: Vec<engine::Rectangle>) -> Vec<engine::Rectangle> {
+        let mut merged_any = true;
+        while merged_any {
+            merged_any = false;
+            'outer: for i in 0..rects.len() {
+                for j in (i + 1)..rects.len() {
+                    if Self::rects_overlap(&rects[i], &rects[j]) {
+                        rects[i] = Self::union_rect(&rects[i], &rects[j]);
+                        rects.remove(j);
+                        merged_any = true;
+                        break 'outer;
                     }
-                    self.screen.get_data_mut()[engine::Position { x: (x+sprite.get_pos().get_x()), y: (y+sprite.get_pos().get_y()) }] = sprite.pixels.get_data()[engine::Position { x: x, y: y }]
                 }
+            }
+        }
+        rects
+    }
 
+    fn rects_overlap(a: &engine::Rectangle, b: &engine::Rectangle) -> bool {
+        !(b.top_left.x > a.bottom_right.x
+            || b.top_left.y > a.bottom_right.y
+            || b.bottom_right.x < a.top_left.x
+            || b.bottom_right.y < a.top_left.y)
+    }
+
+    fn union_rect(a: &engine::Rectangle, b: &engine::Rectangle) -> engine::Rectangle {
+        engine::Rectangle {
+            top_left: engine::Position {
+                x: cmp::min(a.top_left.x, b.top_left.x),
+                y: cmp::min(a.top_left.y, b.top_left.y),
+            },
+            bottom_right: engine::Position {
+                x: cmp::max(a.bottom_right.x, b.bottom_right.x),
+                y: cmp::max(a.bottom_right.y, b.bottom_right.y),
+            },
+        }
+    }
+
+    fn clear_rect(&mut self, rect: &engine::Rectangle) {
+        let bg = self.screen.get_bg();
+        for y in rect.top_left.y..=rect.bottom_right.y {
+            for x in rect.top_left.x..=rect.bottom_right.x {
+                let point = engine::Position { x, y };
+                if self.screen.get_data().in_range(&point) {
+                    self.screen.get_data_mut()[point] = bg;
+                }
             }
         }
+    }
 
-        // TODO update stuff
+    // Takes `screen` separately (rather than `&mut self`) so callers can
+    // still hold a borrow of `self.sprites` for the sprite being blitted
+    fn blit_sprite(screen: &mut engine::Bitmap, sprite: &Sprite) {
+        for y in 0..sprite.pixels.get_data().get_rows() {
+            for x in 0..sprite.pixels.get_data().get_cols() {
+                let point = engine::Position { x: x + sprite.get_pos().get_x(), y: y + sprite.get_pos().get_y() };
+                screen.get_data_mut()[point] = sprite.pixels.get_data()[engine::Position { x, y }];
+            }
+        }
     }
 
-    
+    /// Redraws only the cells that changed since the last `update`, rather
+    /// than resetting and re-blitting the whole board every frame
+    pub fn update(&mut self) {
+        let dirty = self.dirty_rects();
+
+        for rect in &dirty {
+            self.clear_rect(rect);
+        }
+
+        let sprites: Vec<(SpriteHandle, engine::Rectangle, u64)> = self
+            .sprites
+            .iter()
+            .map(|(handle, sprite)| (handle, *sprite.get_bounds(), Self::pixel_fingerprint(sprite)))
+            .collect();
+        for (handle, bounds, _) in &sprites {
+            if dirty.iter().any(|rect| Self::rects_overlap(rect, bounds)) {
+                if let Some(sprite) = self.sprites.get(*handle) {
+                    Self::blit_sprite(&mut self.screen, sprite);
+                }
+            }
+        }
+
+        self.previous_frame = sprites
+            .into_iter()
+            .map(|(handle, bounds, fingerprint)| (handle, (bounds, fingerprint)))
+            .collect();
+    }
+
+
 
 
     // TODO Detect sprite position conflicts?
@@ -208,17 +682,44 @@ impl Board {
     // Sprite FG positions copied in?
     // Then iterate 1 bitmap and check other bitmap?
     // Or just convert using math. Probably way faster but easier to get wrong.
-    pub fn add_sprite(&mut self, newsprite: Sprite) {
-        if self.sprites.len() > 0 {
-            for sprite in self.sprites.iter() {
+    pub fn add_sprite(&mut self, newsprite: Sprite) -> Option<SpriteHandle> {
+        // Broad phase: only the sprites sharing a grid cell with newsprite
+        // can possibly intersect it, so the per-pixel check below only runs
+        // on that small candidate set instead of every sprite on the board
+        for handle in self.grid.candidates(newsprite.get_bounds()) {
+            if let Some(sprite) = self.sprites.get(handle) {
                 if sprite.intersect(&newsprite) {
-                    println!("REJECTED!");
-                    return
+                    return None
                 }
             }
         }
-        println!("Adding");
-        self.sprites.push(newsprite);
+        let bounds = *newsprite.get_bounds();
+        let handle = self.sprites.insert(newsprite);
+        self.index_insert(handle, &bounds);
+        Some(handle)
+    }
+
+    /// Removes a sprite from the board, freeing its handle for reuse
+    ///
+    /// Returns the removed sprite, or `None` if the handle is stale (its
+    /// sprite was already removed).
+    pub fn remove_sprite(&mut self, handle: SpriteHandle) -> Option<Sprite> {
+        let sprite = self.sprites.remove(handle)?;
+        self.index_remove(handle, sprite.get_bounds());
+        Some(sprite)
+    }
+
+    pub fn get_sprite(&self, handle: SpriteHandle) -> Option<&Sprite> {
+        self.sprites.get(handle)
+    }
+
+    pub fn get_sprite_mut(&mut self, handle: SpriteHandle) -> Option<&mut Sprite> {
+        self.sprites.get_mut(handle)
+    }
+
+    /// Presents the current `screen` through the given renderer
+    pub fn render<R: Renderer>(&self, renderer: &mut R) -> io::Result<()> {
+        renderer.present(&self.screen)
     }
 }
 
@@ -430,6 +931,147 @@ mod tests {
         // TODO Check new fg and bg values
     }
 
+    #[test]
+    fn test_board_add_sprite_grid_candidates_are_local() {
+        let cols = 40;
+        let rows = 40;
+        let bg = 0;
+        let fg = 1;
+        let mut b: Board = Board::new(cols, rows, fg, bg);
+        let _ = &b.add_sprite(Sprite::new(2, 3, 4, 5, None));
+        // Far enough away that it shares no grid cell with the first sprite
+        let far = Sprite::new(2, 3, 4, 5, Some(engine::Position { x: 30, y: 30 }));
+        assert_eq!(b.grid.candidates(far.get_bounds()).len(), 0);
+        let _ = &b.add_sprite(far);
+        assert_eq!(b.sprites.len(), 2);
+    }
+
+    #[test]
+    fn test_board_remove_sprite_frees_handle_for_reuse() {
+        let cols = 10;
+        let rows = 10;
+        let bg = 0;
+        let fg = 1;
+        let mut b: Board = Board::new(cols, rows, fg, bg);
+        let handle = b.add_sprite(Sprite::new(2, 3, 4, 5, None)).expect("should add");
+        assert_eq!(b.sprites.len(), 1);
+
+        let removed = b.remove_sprite(handle).expect("should remove");
+        assert_eq!(removed.get_pos().get_x(), 0);
+        assert_eq!(b.sprites.len(), 0);
+
+        // The handle is stale now; it must not resolve to whatever reuses its slot
+        assert!(b.get_sprite(handle).is_none());
+        assert!(b.remove_sprite(handle).is_none());
+
+        let reused = b
+            .add_sprite(Sprite::new(2, 3, 8, 7, Some(engine::Position { x: 5, y: 5 })))
+            .expect("should add");
+        assert!(b.get_sprite(reused).is_some());
+        assert!(b.get_sprite(handle).is_none());
+    }
+
+    #[test]
+    fn test_board_update_redraws_moved_sprite_and_clears_old_spot() {
+        let cols = 10;
+        let rows = 10;
+        let bg = 0;
+        let fg = 1;
+        let mut b: Board = Board::new(cols, rows, fg, bg);
+        let mut sprite = Sprite::new(1, 1, fg, bg, Some(engine::Position { x: 2, y: 2 }));
+        sprite.pixels.get_data_mut()[engine::Position { x: 0, y: 0 }] = fg;
+        let handle = b.add_sprite(sprite).expect("should add");
+        b.update();
+        assert_eq!(b.screen.get_data()[engine::Position { x: 2, y: 2 }], fg);
+
+        assert!(b.move_sprite(handle, engine::Position { x: 6, y: 6 }));
+        b.update();
+        assert_eq!(b.screen.get_data()[engine::Position { x: 2, y: 2 }], bg);
+        assert_eq!(b.screen.get_data()[engine::Position { x: 6, y: 6 }], fg);
+    }
+
+    #[test]
+    fn test_board_update_skips_unchanged_sprites() {
+        let cols = 10;
+        let rows = 10;
+        let bg = 0;
+        let fg = 1;
+        let mut b: Board = Board::new(cols, rows, fg, bg);
+        let _ = b.add_sprite(Sprite::new(1, 1, fg, bg, Some(engine::Position { x: 2, y: 2 })));
+        b.update();
+        assert_eq!(b.dirty_rects().len(), 0);
+    }
+
+    #[test]
+    fn test_board_update_redraws_sprite_repainted_in_place() {
+        let cols = 10;
+        let rows = 10;
+        let bg = 0;
+        let fg = 1;
+        let mut b: Board = Board::new(cols, rows, fg, bg);
+        let handle = b
+            .add_sprite(Sprite::new(1, 1, fg, bg, Some(engine::Position { x: 2, y: 2 })))
+            .expect("should add");
+        b.update();
+        assert_eq!(b.screen.get_data()[engine::Position { x: 2, y: 2 }], bg);
+
+        // Same bounds, new pixels -- e.g. a marching animation frame
+        let sprite = b.get_sprite_mut(handle).expect("sprite should still exist");
+        sprite.pixels.get_data_mut()[engine::Position { x: 0, y: 0 }] = fg;
+        assert_eq!(b.dirty_rects().len(), 1);
+
+        b.update();
+        assert_eq!(b.screen.get_data()[engine::Position { x: 2, y: 2 }], fg);
+    }
+
+    #[test]
+    fn test_board_sprites_overlapping_query_rect() {
+        let cols = 10;
+        let rows = 10;
+        let bg = 0;
+        let fg = 1;
+        let mut b: Board = Board::new(cols, rows, fg, bg);
+        let left = b
+            .add_sprite(Sprite::new(2, 2, fg, bg, Some(engine::Position { x: 0, y: 0 })))
+            .expect("should add");
+        let right = b
+            .add_sprite(Sprite::new(2, 2, fg, bg, Some(engine::Position { x: 5, y: 0 })))
+            .expect("should add");
+
+        let query = engine::Rectangle {
+            top_left: engine::Position { x: 0, y: 0 },
+            bottom_right: engine::Position { x: 1, y: 1 },
+        };
+        let found = b.sprites_overlapping(&query);
+        assert_eq!(found, vec![left]);
+        assert!(!found.contains(&right));
+    }
+
+    #[test]
+    fn test_board_sprites_overlapping_shares_identical_intervals() {
+        let cols = 20;
+        let rows = 20;
+        let bg = 0;
+        let fg = 1;
+        let mut b: Board = Board::new(cols, rows, fg, bg);
+        // Two sprites sharing an identical y-interval (like a row of aligned
+        // invaders), at different x positions so they don't collide. The
+        // y-axis BTreeMap entry for that interval must hold both handles.
+        let left = b
+            .add_sprite(Sprite::new(2, 2, fg, bg, Some(engine::Position { x: 0, y: 0 })))
+            .expect("should add");
+        let right = b
+            .add_sprite(Sprite::new(2, 2, fg, bg, Some(engine::Position { x: 5, y: 0 })))
+            .expect("should add");
+
+        let query = engine::Rectangle {
+            top_left: engine::Position { x: 0, y: 0 },
+            bottom_right: engine::Position { x: 6, y: 1 },
+        };
+        let found: HashSet<SpriteHandle> = b.sprites_overlapping(&query).into_iter().collect();
+        assert_eq!(found, [left, right].into_iter().collect());
+    }
+
     #[test]
     fn test_board_build_str() {
         let data = r#"
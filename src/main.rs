@@ -11,6 +11,7 @@ use crate::engine::Bitmap;
 
 mod engine;
 mod game;
+mod render;
 
 
 fn main() {
@@ -0,0 +1,172 @@
+/********************************************************************
+* Copyright (c) 2021-2022, Eric Mackay
+* All rights reserved.
+*
+* This source code is licensed under the BSD-style license found in the
+* LICENSE file in the root directory of this source tree.
+********************************************************************/
+
+use crate::engine::Bitmap;
+use std::io::{self, Write};
+
+/// Something that can present a `Bitmap` frame to the player
+pub trait Renderer {
+    fn present(&mut self, frame: &Bitmap) -> io::Result<()>;
+}
+
+/// Renders a `Bitmap` to the terminal
+///
+/// Keeps a shadow copy of the last presented frame and, on each `present`,
+/// only emits a cursor-move plus a glyph write for the cells that changed
+/// since then, instead of redrawing the whole screen every frame.
+pub struct TerminalRenderer<W: Write> {
+    out: W,
+    // None until the first frame is presented, since there is nothing yet
+    // to diff against
+    shadow: Option<Vec<u8>>,
+    cols: usize,
+    rows: usize,
+}
+
+impl TerminalRenderer<io::Stdout> {
+    pub fn new() -> io::Result<Self> {
+        let mut renderer = Self {
+            out: io::stdout(),
+            shadow: None,
+            cols: 0,
+            rows: 0,
+        };
+        renderer.enter()?;
+        Ok(renderer)
+    }
+}
+
+impl<W: Write> TerminalRenderer<W> {
+    // Switches to the alternate screen buffer and hides the cursor, so the
+    // game has the whole terminal to itself and doesn't leave a blinking
+    // cursor behind as cells are updated
+    fn enter(&mut self) -> io::Result<()> {
+        write!(self.out, "\x1b[?1049h\x1b[?25l")?;
+        self.out.flush()
+    }
+
+    fn leave(&mut self) -> io::Result<()> {
+        write!(self.out, "\x1b[?25h\x1b[?1049l")?;
+        self.out.flush()
+    }
+
+    fn move_cursor(&mut self, x: usize, y: usize) -> io::Result<()> {
+        // Escape sequences are 1-indexed, Positions are 0-indexed
+        write!(self.out, "\x1b[{};{}H", y + 1, x + 1)
+    }
+
+    // Mirrors Bitmap's own Display impl: fg draws as '#', bg as ' ', and
+    // anything else (shouldn't normally happen) as '?'
+    fn glyph(frame: &Bitmap, value: u8) -> char {
+        if value == frame.get_fg() {
+            '#'
+        } else if value == frame.get_bg() {
+            ' '
+        } else {
+            '?'
+        }
+    }
+
+    // Maps a cell's raw value onto the xterm 256-color palette (values
+    // happen to line up with palette indices 0-255), so distinct entity
+    // values render as distinct colors instead of a single monochrome glyph
+    fn color(value: u8) -> String {
+        format!("\x1b[38;5;{}m", value)
+    }
+}
+
+impl<W: Write> Renderer for TerminalRenderer<W> {
+    fn present(&mut self, frame: &Bitmap) -> io::Result<()> {
+        let data = frame.get_data();
+        let cols = data.get_cols();
+        let rows = data.get_rows();
+
+        // First frame, or the board changed size: there's no valid shadow
+        // buffer to diff against, so every cell counts as changed
+        let first_frame = self.shadow.is_none() || self.cols != cols || self.rows != rows;
+        if first_frame {
+            self.shadow = Some(vec![0; cols * rows]);
+            self.cols = cols;
+            self.rows = rows;
+        }
+
+        let elements = data.get_elements();
+        for y in 0..rows {
+            for x in 0..cols {
+                let index = y * cols + x;
+                let value = elements[index];
+                let changed = first_frame || self.shadow.as_ref().unwrap()[index] != value;
+                if changed {
+                    self.move_cursor(x, y)?;
+                    write!(self.out, "{}{}\x1b[0m", Self::color(value), Self::glyph(frame, value))?;
+                    self.shadow.as_mut().unwrap()[index] = value;
+                }
+            }
+        }
+        self.out.flush()
+    }
+}
+
+impl<W: Write> Drop for TerminalRenderer<W> {
+    fn drop(&mut self) {
+        // Best-effort: there's nothing useful to do if restoring the
+        // terminal on the way out fails
+        let _ = self.leave();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine;
+
+    // Bypasses TerminalRenderer::new (which always targets real stdout) so
+    // tests can inspect what gets written
+    fn test_renderer() -> TerminalRenderer<Vec<u8>> {
+        TerminalRenderer {
+            out: Vec::new(),
+            shadow: None,
+            cols: 0,
+            rows: 0,
+        }
+    }
+
+    fn written(renderer: &TerminalRenderer<Vec<u8>>) -> String {
+        String::from_utf8(renderer.out.clone()).expect("output should be valid utf8")
+    }
+
+    #[test]
+    fn test_present_first_frame_draws_every_cell() {
+        let bmp = Bitmap::new(2, 2, 1, 0);
+        let mut renderer = test_renderer();
+        renderer.present(&bmp).expect("present should succeed");
+        // 4 cells, each preceded by a cursor move plus a color set/reset pair
+        assert_eq!(written(&renderer).matches("\x1b[").count(), 4 * 3);
+    }
+
+    #[test]
+    fn test_present_second_frame_only_draws_changed_cells() {
+        let mut bmp = Bitmap::new(2, 2, 1, 0);
+        let mut renderer = test_renderer();
+        renderer.present(&bmp).expect("present should succeed");
+
+        bmp.get_data_mut()[engine::Position { x: 0, y: 0 }] = bmp.get_fg();
+        renderer.present(&bmp).expect("present should succeed");
+
+        let second_frame_start = written(&renderer).matches("\x1b[").count();
+        assert_eq!(second_frame_start, 4 * 3 + 3);
+    }
+
+    #[test]
+    fn test_present_emits_distinct_color_per_value() {
+        let bmp = Bitmap::new(1, 1, 1, 0);
+        let mut renderer = test_renderer();
+        renderer.present(&bmp).expect("present should succeed");
+        assert!(written(&renderer).contains("\x1b[38;5;0m"));
+    }
+}